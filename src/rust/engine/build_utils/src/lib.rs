@@ -1,7 +1,74 @@
 use std::env;
+use std::error;
+use std::fmt;
 use std::io;
 use std::ops::Deref;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+mod scm;
+mod workspace;
+
+pub use crate::scm::{Scm, ScmError};
+pub use crate::workspace::Workspace;
+
+/// An environment variable that, when set, short-circuits the upward search for a build root
+/// and is used directly, provided it contains the sentinel.
+const BUILDROOT_OVERRIDE_VAR: &str = "PANTS_BUILDROOT_OVERRIDE";
+
+/// The sentinel files `find()` and `find_from()` look for, in preference order: the launcher
+/// script used by the current Pants convention, and the config file used by newer layouts.
+pub(crate) const DEFAULT_SENTINELS: &[&str] = &["pants", "pants.toml"];
+
+/// An error produced while locating the Pants build root.
+#[derive(Debug)]
+pub enum BuildRootError {
+  /// The current working directory could not be read.
+  CurrentDirUnavailable(io::Error),
+  /// The `PANTS_BUILDROOT_OVERRIDE` environment variable was set, but the directory it names
+  /// does not contain a build root sentinel.
+  InvalidOverride { dir: PathBuf },
+  /// The search walked up to the filesystem root without finding a build root sentinel.
+  NotFound { start: PathBuf },
+}
+
+impl fmt::Display for BuildRootError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      BuildRootError::CurrentDirUnavailable(err) => {
+        write!(f, "Failed to read the current directory: {}", err)
+      }
+      BuildRootError::InvalidOverride { dir } => write!(
+        f,
+        "{} was set to {:?}, but it does not contain a build root sentinel.",
+        BUILDROOT_OVERRIDE_VAR, dir
+      ),
+      BuildRootError::NotFound { start } => write!(
+        f,
+        "Failed to find a Pants build root starting from {:?}",
+        start
+      ),
+    }
+  }
+}
+
+impl error::Error for BuildRootError {
+  fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+    match self {
+      BuildRootError::CurrentDirUnavailable(err) => Some(err),
+      _ => None,
+    }
+  }
+}
+
+/// Preserves source compatibility for callers that use `?` against an `io::Result`.
+impl From<BuildRootError> for io::Error {
+  fn from(err: BuildRootError) -> Self {
+    match err {
+      BuildRootError::CurrentDirUnavailable(err) => err,
+      _ => io::Error::new(io::ErrorKind::NotFound, err.to_string()),
+    }
+  }
+}
 
 pub struct BuildRoot(PathBuf);
 
@@ -16,10 +83,14 @@ impl Deref for BuildRoot {
 impl BuildRoot {
   /// Finds the Pants build root containing the current working directory.
   ///
+  /// If the `PANTS_BUILDROOT_OVERRIDE` environment variable is set, it is used directly instead
+  /// of searching, provided it contains a build root sentinel.
+  ///
   /// # Errors
   ///
-  /// If finding the current working directory fails or the search for the Pants build root finds
-  /// none.
+  /// If `PANTS_BUILDROOT_OVERRIDE` is set but does not contain a build root sentinel
+  /// (`InvalidOverride`), if the current working directory cannot be read
+  /// (`CurrentDirUnavailable`), or if the search for the Pants build root finds none (`NotFound`).
   ///
   /// # Examples
   ///
@@ -32,43 +103,144 @@ impl BuildRoot {
   /// let pants = build_root.join("pants");
   /// assert!(pants.exists());
   /// ```
-  pub fn find() -> io::Result<BuildRoot> {
-    let current_dir = env::current_dir()?;
-    let mut here = current_dir.as_path();
+  pub fn find() -> Result<BuildRoot, BuildRootError> {
+    if let Some(override_dir) = env::var_os(BUILDROOT_OVERRIDE_VAR) {
+      let here = PathBuf::from(override_dir);
+      return if has_any_sentinel(&here, DEFAULT_SENTINELS) {
+        Ok(BuildRoot(here))
+      } else {
+        Err(BuildRootError::InvalidOverride { dir: here })
+      };
+    }
+    let current_dir = env::current_dir().map_err(BuildRootError::CurrentDirUnavailable)?;
+    Self::find_from(&current_dir)
+  }
+
+  /// Finds the Pants build root containing `start`, without consulting the current working
+  /// directory or the `PANTS_BUILDROOT_OVERRIDE` environment variable.
+  ///
+  /// # Errors
+  ///
+  /// If the search for the Pants build root walks up to the filesystem root without finding one.
+  pub fn find_from(start: &Path) -> Result<BuildRoot, BuildRootError> {
+    Self::find_with_sentinels(start, DEFAULT_SENTINELS)
+  }
+
+  /// Finds the nearest ancestor of `start` (inclusive) containing any of `sentinels`, trying
+  /// them in the order given. This lets callers anchor on something other than the `pants`
+  /// launcher script, e.g. a `pants.toml` or a `.pants.d` directory.
+  ///
+  /// # Errors
+  ///
+  /// If the search walks up to the filesystem root without finding a directory containing any
+  /// of `sentinels`.
+  pub fn find_with_sentinels(
+    start: &Path,
+    sentinels: &[&str],
+  ) -> Result<BuildRoot, BuildRootError> {
+    let mut here = start;
     loop {
-      if here.join("pants").exists() {
+      if has_any_sentinel(here, sentinels) {
         return Ok(BuildRoot(here.to_path_buf()));
       } else if let Some(parent) = here.parent() {
         here = parent;
       } else {
-        return Err(io::Error::new(
-          io::ErrorKind::NotFound,
-          format!("Failed to find build root starting from {:?}", current_dir),
-        ));
+        return Err(BuildRootError::NotFound {
+          start: start.to_path_buf(),
+        });
       }
     }
   }
 }
 
+pub(crate) fn has_any_sentinel(dir: &Path, sentinels: &[&str]) -> bool {
+  sentinels.iter().any(|sentinel| dir.join(sentinel).exists())
+}
+
 #[cfg(test)]
 mod build_utils_test {
-  use super::BuildRoot;
+  use super::{BuildRoot, BuildRootError, BUILDROOT_OVERRIDE_VAR};
 
+  use std::env;
+  use std::fs;
   use std::path::PathBuf;
   use std::process::Command;
+  use std::sync::Mutex;
 
-  #[test]
-  fn find() {
+  /// Tests that read or mutate `BUILDROOT_OVERRIDE_VAR` must serialize against each other,
+  /// since the environment is process-global state shared across concurrently running tests.
+  static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+  fn git_show_toplevel() -> PathBuf {
     let result = Command::new("git")
       .args(&["rev-parse", "--show-toplevel"])
       .output()
       .expect("Expected `git` to be on the `PATH` and this test to be run in a git repository.");
 
-    let root_dir: PathBuf = String::from_utf8(result.stdout)
+    String::from_utf8(result.stdout)
       .expect("The Pants build root is not a valid UTF-8 path.")
       .trim()
-      .into();
+      .into()
+  }
+
+  #[test]
+  fn find() {
+    let _guard = ENV_MUTEX.lock().unwrap();
+    env::remove_var(BUILDROOT_OVERRIDE_VAR);
+
+    assert_eq!(*BuildRoot::find().unwrap(), git_show_toplevel())
+  }
+
+  #[test]
+  fn find_from_nested_directory() {
+    let root_dir = git_show_toplevel();
+    let nested = root_dir.join("src").join("rust");
+    assert_eq!(*BuildRoot::find_from(&nested).unwrap(), root_dir)
+  }
+
+  #[test]
+  fn find_honors_buildroot_override_when_sentinel_present() {
+    let _guard = ENV_MUTEX.lock().unwrap();
+
+    let dir = env::temp_dir().join("build_utils_test_find_override_valid");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("pants"), b"").unwrap();
+
+    env::set_var(BUILDROOT_OVERRIDE_VAR, &dir);
+    let result = BuildRoot::find();
+    env::remove_var(BUILDROOT_OVERRIDE_VAR);
+
+    assert_eq!(*result.unwrap(), dir);
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn find_rejects_buildroot_override_without_sentinel() {
+    let _guard = ENV_MUTEX.lock().unwrap();
+
+    let dir = env::temp_dir().join("build_utils_test_find_override_invalid");
+    fs::create_dir_all(&dir).unwrap();
+    let _ = fs::remove_file(dir.join("pants"));
+
+    env::set_var(BUILDROOT_OVERRIDE_VAR, &dir);
+    let result = BuildRoot::find();
+    env::remove_var(BUILDROOT_OVERRIDE_VAR);
+
+    assert!(matches!(result, Err(BuildRootError::InvalidOverride { dir: d }) if d == dir));
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn find_with_sentinels_matches_any_listed_sentinel() {
+    let dir = env::temp_dir().join("build_utils_test_find_with_sentinels");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("pants.toml"), b"").unwrap();
+
+    assert_eq!(
+      *BuildRoot::find_with_sentinels(&dir, &["pants", "pants.toml"]).unwrap(),
+      dir
+    );
 
-    assert_eq!(*BuildRoot::find().unwrap(), root_dir)
+    fs::remove_dir_all(&dir).unwrap();
   }
 }
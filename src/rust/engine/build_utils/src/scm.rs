@@ -0,0 +1,222 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+use crate::BuildRoot;
+
+/// An error encountered while shelling out to `git`.
+#[derive(Debug)]
+pub enum ScmError {
+  /// The `git` binary could not be spawned at all (e.g. it is not on the `PATH`).
+  Io(io::Error),
+  /// `git` was spawned but exited with a nonzero status.
+  CommandFailed { command: String, stderr: String },
+}
+
+impl fmt::Display for ScmError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ScmError::Io(err) => write!(f, "Failed to spawn `git`: {}", err),
+      ScmError::CommandFailed { command, stderr } => {
+        write!(f, "`{}` failed: {}", command, stderr.trim())
+      }
+    }
+  }
+}
+
+impl std::error::Error for ScmError {}
+
+impl From<io::Error> for ScmError {
+  fn from(err: io::Error) -> Self {
+    ScmError::Io(err)
+  }
+}
+
+impl From<ScmError> for io::Error {
+  fn from(err: ScmError) -> Self {
+    io::Error::other(err.to_string())
+  }
+}
+
+/// A handle to the git checkout backing a [`BuildRoot`], used to answer "what changed"
+/// questions for `--changed-since` style flags.
+pub struct Scm {
+  root: PathBuf,
+}
+
+impl Scm {
+  /// Detects the git repository backing `build_root`, by confirming that `git` considers it
+  /// part of a work tree.
+  pub fn detect(build_root: &BuildRoot) -> io::Result<Scm> {
+    let scm = Scm {
+      root: build_root.to_path_buf(),
+    };
+    scm.run(&["rev-parse", "--show-toplevel"])?;
+    Ok(scm)
+  }
+
+  fn run(&self, args: &[&str]) -> Result<Output, ScmError> {
+    let output = Command::new("git")
+      .args(args)
+      .current_dir(&self.root)
+      .output()?;
+    if !output.status.success() {
+      return Err(ScmError::CommandFailed {
+        command: format!("git {}", args.join(" ")),
+        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+      });
+    }
+    Ok(output)
+  }
+
+  /// Runs `git` and returns its stdout verbatim, for output whose lines must be parsed with
+  /// fixed-offset or otherwise whitespace-sensitive logic (e.g. `git status --porcelain`'s
+  /// leading status columns). Trimming the whole blob here would eat the first line's leading
+  /// whitespace and corrupt it.
+  fn run_to_raw_string(&self, args: &[&str]) -> Result<String, ScmError> {
+    let output = self.run(args)?;
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+  }
+
+  /// Runs `git` and returns its stdout with leading/trailing whitespace trimmed, for output
+  /// that is a single line (a sha, a path) where no offset-sensitive parsing follows.
+  fn run_to_string(&self, args: &[&str]) -> Result<String, ScmError> {
+    Ok(self.run_to_raw_string(args)?.trim().to_owned())
+  }
+
+  /// Returns the files changed relative to `since`, which may be any committish (a ref, a
+  /// sha, or the output of [`Scm::merge_base`]). This includes staged, unstaged and untracked
+  /// files, deduplicated and expressed as absolute paths rooted at the build root.
+  pub fn changed_files(&self, since: &str) -> io::Result<Vec<PathBuf>> {
+    let diffed = self.run_to_raw_string(&["diff", "--name-only", since])?;
+    let status = self.run_to_raw_string(&["status", "--porcelain"])?;
+
+    let mut changed = BTreeSet::new();
+    changed.extend(diffed.lines().filter(|line| !line.is_empty()).map(str::to_owned));
+    changed.extend(parse_porcelain_paths(&status));
+
+    Ok(
+      changed
+        .into_iter()
+        .map(|relpath| self.root.join(relpath))
+        .collect(),
+    )
+  }
+
+  /// Finds the best common ancestor commit of `a` and `b`, via `git merge-base`.
+  pub fn merge_base(&self, a: &str, b: &str) -> io::Result<String> {
+    Ok(self.run_to_string(&["merge-base", a, b])?)
+  }
+
+  /// Returns the sha of the commit currently checked out.
+  pub fn current_rev(&self) -> io::Result<String> {
+    Ok(self.run_to_string(&["rev-parse", "HEAD"])?)
+  }
+}
+
+/// Parses the repo-relative paths out of `git status --porcelain` output, which formats each
+/// entry as `XY PATH` (or `XY PATH1 -> PATH2` for renames, where we care only about the new
+/// path).
+fn parse_porcelain_paths(porcelain: &str) -> Vec<String> {
+  porcelain
+    .lines()
+    .filter_map(|line| line.get(3..))
+    .map(|path| match path.find(" -> ") {
+      Some(index) => &path[index + 4..],
+      None => path,
+    })
+    .map(str::to_owned)
+    .collect()
+}
+
+#[cfg(test)]
+mod scm_test {
+  use super::{parse_porcelain_paths, Scm};
+  use crate::BuildRoot;
+
+  use std::fs;
+  use std::path::PathBuf;
+  use std::process::Command;
+
+  #[test]
+  fn parses_porcelain_entries_including_renames() {
+    let porcelain = " M src/rust/engine/build_utils/src/lib.rs\n\
+                      ?? src/rust/engine/build_utils/src/scm.rs\n\
+                      R  old/path.rs -> new/path.rs\n";
+    assert_eq!(
+      parse_porcelain_paths(porcelain),
+      vec![
+        "src/rust/engine/build_utils/src/lib.rs".to_owned(),
+        "src/rust/engine/build_utils/src/scm.rs".to_owned(),
+        "new/path.rs".to_owned(),
+      ]
+    );
+  }
+
+  fn git(repo: &PathBuf, args: &[&str]) {
+    let status = Command::new("git")
+      .args(args)
+      .current_dir(repo)
+      .status()
+      .expect("Expected `git` to be on the `PATH`.");
+    assert!(status.success(), "`git {}` failed", args.join(" "));
+  }
+
+  /// Inits a scratch git repo (also tagged with the `pants` sentinel, so it doubles as a
+  /// `BuildRoot`) with a single commit adding `a.txt` and `c.txt`.
+  fn init_repo_with_initial_commit() -> PathBuf {
+    let repo = std::env::temp_dir().join(format!(
+      "build_utils_scm_test_{:?}",
+      std::thread::current().id()
+    ));
+    let _ = fs::remove_dir_all(&repo);
+    fs::create_dir_all(&repo).unwrap();
+
+    git(&repo, &["init", "-q"]);
+    git(&repo, &["config", "user.email", "test@example.com"]);
+    git(&repo, &["config", "user.name", "Test"]);
+
+    fs::write(repo.join("pants"), b"").unwrap();
+    fs::write(repo.join("a.txt"), b"original\n").unwrap();
+    fs::write(repo.join("c.txt"), b"original\n").unwrap();
+    git(&repo, &["add", "."]);
+    git(&repo, &["commit", "-q", "-m", "initial"]);
+
+    repo
+  }
+
+  #[test]
+  fn changed_files_includes_modified_staged_and_untracked() {
+    let repo = init_repo_with_initial_commit();
+
+    // Unstaged modification: the single most common `git status --porcelain` case, and the one
+    // whose leading-space status column a blanket `.trim()` would corrupt.
+    fs::write(repo.join("a.txt"), b"changed\n").unwrap();
+    // Staged modification.
+    fs::write(repo.join("c.txt"), b"changed\n").unwrap();
+    git(&repo, &["add", "c.txt"]);
+    // Untracked file.
+    fs::write(repo.join("b.txt"), b"new\n").unwrap();
+
+    let scm = Scm::detect(&BuildRoot::find_from(&repo).unwrap()).unwrap();
+    let changed = scm.changed_files("HEAD").unwrap();
+
+    let mut relative: Vec<String> = changed
+      .iter()
+      .map(|p| {
+        p.strip_prefix(&repo)
+          .unwrap()
+          .to_str()
+          .unwrap()
+          .to_owned()
+      })
+      .collect();
+    relative.sort();
+
+    assert_eq!(relative, vec!["a.txt", "b.txt", "c.txt"]);
+
+    fs::remove_dir_all(&repo).unwrap();
+  }
+}
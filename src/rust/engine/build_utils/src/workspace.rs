@@ -0,0 +1,189 @@
+use std::cell::OnceCell;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{has_any_sentinel, BuildRoot, BuildRootError, Scm, DEFAULT_SENTINELS};
+
+/// A long-lived handle for tooling that repeatedly needs to know where the build root is, what
+/// revision is checked out, or what subprojects exist, without re-walking the filesystem or
+/// re-spawning `git` on every call. Expensive lookups are computed at most once, the first time
+/// they are asked for, and cached for the lifetime of the `Workspace`.
+pub struct Workspace {
+  build_root: BuildRoot,
+  current_dir: PathBuf,
+  scm: OnceCell<Option<Scm>>,
+  current_rev: OnceCell<Option<String>>,
+  subprojects: OnceCell<Vec<PathBuf>>,
+}
+
+impl Workspace {
+  /// Builds a `Workspace` anchored at the build root that `BuildRoot::find()` resolves: the one
+  /// containing the current working directory, or the `PANTS_BUILDROOT_OVERRIDE` directory when
+  /// that variable is set.
+  pub fn current() -> Result<Workspace, BuildRootError> {
+    let current_dir = env::current_dir().map_err(BuildRootError::CurrentDirUnavailable)?;
+    let build_root = BuildRoot::find()?;
+    Ok(Workspace {
+      build_root,
+      current_dir,
+      scm: OnceCell::new(),
+      current_rev: OnceCell::new(),
+      subprojects: OnceCell::new(),
+    })
+  }
+
+  /// The build root this workspace is anchored at.
+  pub fn build_root(&self) -> &BuildRoot {
+    &self.build_root
+  }
+
+  /// The git handle for this workspace, or `None` if the build root is not a git checkout.
+  /// Detected at most once and cached thereafter.
+  pub fn scm(&self) -> Option<&Scm> {
+    self
+      .scm
+      .get_or_init(|| Scm::detect(&self.build_root).ok())
+      .as_ref()
+  }
+
+  /// The repo's current revision, or `None` if there is no `scm()`. Resolved at most once and
+  /// cached thereafter.
+  pub fn current_rev(&self) -> Option<&str> {
+    self
+      .current_rev
+      .get_or_init(|| self.scm().and_then(|scm| scm.current_rev().ok()))
+      .as_deref()
+  }
+
+  /// The absolute paths of nested subproject roots beneath the build root, identified by the
+  /// same sentinel files as `BuildRoot` itself. Scanned at most once and cached thereafter.
+  pub fn subprojects(&self) -> &[PathBuf] {
+    self
+      .subprojects
+      .get_or_init(|| find_subprojects(&self.build_root))
+  }
+
+  /// The original current working directory, expressed relative to the build root, or `None` if
+  /// it is not actually rooted under the build root (e.g. `PANTS_BUILDROOT_OVERRIDE` pointed
+  /// somewhere unrelated to the current working directory).
+  pub fn current_dir_relative(&self) -> Option<&Path> {
+    self.current_dir.strip_prefix(&*self.build_root).ok()
+  }
+}
+
+/// Directory names that are never worth descending into while scanning for subprojects: they
+/// are build outputs or vendored dependency trees that can be arbitrarily large and never
+/// themselves contain a build root sentinel.
+const PRUNED_DIR_NAMES: &[&str] = &["target", "node_modules", "dist", "build"];
+
+/// Walks the tree beneath `root` looking for nested directories that themselves contain a
+/// build root sentinel, skipping hidden directories (e.g. `.git`) and known build-output /
+/// vendored-dependency directories (see `PRUNED_DIR_NAMES`) along the way.
+fn find_subprojects(root: &Path) -> Vec<PathBuf> {
+  let mut subprojects = Vec::new();
+  visit_dirs(root, root, &mut subprojects);
+  subprojects
+}
+
+fn visit_dirs(root: &Path, dir: &Path, subprojects: &mut Vec<PathBuf>) {
+  let entries = match fs::read_dir(dir) {
+    Ok(entries) => entries,
+    Err(_) => return,
+  };
+  for entry in entries.flatten() {
+    let is_real_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+    if !is_real_dir {
+      // Don't follow symlinks: a symlink back up the tree would otherwise recurse forever.
+      continue;
+    }
+    let path = entry.path();
+    let name = path.file_name().and_then(|name| name.to_str());
+    let is_hidden = name.is_some_and(|name| name.starts_with('.'));
+    let is_pruned = name.is_some_and(|name| PRUNED_DIR_NAMES.contains(&name));
+    if is_hidden || is_pruned {
+      continue;
+    }
+    if path != root && has_any_sentinel(&path, DEFAULT_SENTINELS) {
+      subprojects.push(path);
+      continue;
+    }
+    visit_dirs(root, &path, subprojects);
+  }
+}
+
+#[cfg(test)]
+mod workspace_test {
+  use super::{find_subprojects, Workspace};
+  use crate::BuildRoot;
+
+  use std::cell::OnceCell;
+  use std::fs;
+  use std::path::PathBuf;
+
+  fn workspace_at(build_root: PathBuf, current_dir: PathBuf) -> Workspace {
+    Workspace {
+      build_root: BuildRoot::find_from(&build_root).unwrap(),
+      current_dir,
+      scm: OnceCell::new(),
+      current_rev: OnceCell::new(),
+      subprojects: OnceCell::new(),
+    }
+  }
+
+  #[test]
+  fn subprojects_finds_nested_sentinels_but_not_pruned_or_hidden_dirs() {
+    let root = std::env::temp_dir().join("build_utils_test_subprojects");
+    let _ = fs::remove_dir_all(&root);
+
+    fs::create_dir_all(root.join("sub1")).unwrap();
+    fs::write(root.join("sub1").join("pants.toml"), b"").unwrap();
+    // Nested past a matched subproject: should not itself be reported.
+    fs::create_dir_all(root.join("sub1").join("nested")).unwrap();
+    fs::write(root.join("sub1").join("nested").join("pants"), b"").unwrap();
+
+    fs::create_dir_all(root.join("plain").join("subsub")).unwrap();
+    fs::write(root.join("plain").join("subsub").join("pants"), b"").unwrap();
+
+    fs::create_dir_all(root.join("node_modules").join("pkg")).unwrap();
+    fs::write(root.join("node_modules").join("pkg").join("pants"), b"").unwrap();
+
+    fs::create_dir_all(root.join(".git")).unwrap();
+    fs::write(root.join(".git").join("pants"), b"").unwrap();
+
+    fs::write(root.join("pants"), b"").unwrap();
+
+    let mut found = find_subprojects(&root);
+    found.sort();
+
+    assert_eq!(
+      found,
+      vec![
+        root.join("plain").join("subsub"),
+        root.join("sub1"),
+      ]
+    );
+
+    fs::remove_dir_all(&root).unwrap();
+  }
+
+  #[test]
+  fn current_dir_relative_is_none_outside_build_root() {
+    let root = std::env::temp_dir().join("build_utils_test_current_dir_relative");
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(root.join("sub")).unwrap();
+    fs::write(root.join("pants"), b"").unwrap();
+
+    let workspace = workspace_at(root.clone(), root.join("sub"));
+    assert_eq!(
+      workspace.current_dir_relative(),
+      Some(PathBuf::from("sub").as_path())
+    );
+
+    let unrelated = std::env::temp_dir().join("build_utils_test_unrelated");
+    let workspace = workspace_at(root.clone(), unrelated);
+    assert_eq!(workspace.current_dir_relative(), None);
+
+    fs::remove_dir_all(&root).unwrap();
+  }
+}